@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{self, BufRead, BufReader, Read},
     os::unix::fs::FileExt,
@@ -7,9 +8,11 @@ use std::{
     sync::{Arc, atomic::{AtomicUsize, Ordering}},
 };
 
-use blake3::{hash, Hasher};
+use blake3::{hash, Hasher as Blake3Hasher};
 use crossbeam_channel::bounded;
+use crc32fast::Hasher as Crc32Hasher;
 use walkdir::WalkDir;
+use xxhash_rust::xxh3::Xxh3;
 
 use mimalloc::MiMalloc;
 #[global_allocator]
@@ -20,6 +23,119 @@ use option::Option as Opt;
  
 const MIN_READ_SIZE: usize = 1 << 20; // 1 MiB
 
+/// A pluggable digest backend so `hash_file` and the sampled path can run
+/// over whichever algorithm `--algo` selects.
+trait Hasher {
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self: Box<Self>) -> String;
+}
+
+impl Hasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        Blake3Hasher::update(self, data);
+    }
+    fn finalize(self: Box<Self>) -> String {
+        (*self).finalize().to_hex().to_string()
+    }
+}
+
+impl Hasher for Xxh3 {
+    fn update(&mut self, data: &[u8]) {
+        Xxh3::update(self, data);
+    }
+    fn finalize(self: Box<Self>) -> String {
+        format!("{:016x}", self.digest())
+    }
+}
+
+impl Hasher for Crc32Hasher {
+    fn update(&mut self, data: &[u8]) {
+        Crc32Hasher::update(self, data);
+    }
+    fn finalize(self: Box<Self>) -> String {
+        format!("{:08x}", self.finalize())
+    }
+}
+
+impl Hasher for openssl::hash::Hasher {
+    fn update(&mut self, data: &[u8]) {
+        openssl::hash::Hasher::update(self, data).expect("openssl hasher update");
+    }
+    fn finalize(self: Box<Self>) -> String {
+        hex::encode((*self).finish().expect("openssl hasher finish"))
+    }
+}
+
+/// Builds a blake3 hasher, honoring `--keyed`/`--derive-key` when set.
+fn new_blake3_hasher(keyed: Option<&[u8; 32]>, derive_key: Option<&str>) -> Blake3Hasher {
+    if let Some(key) = keyed {
+        Blake3Hasher::new_keyed(key)
+    } else if let Some(context) = derive_key {
+        Blake3Hasher::new_derive_key(context)
+    } else {
+        Blake3Hasher::new()
+    }
+}
+
+/// Builds the trait-object hasher for `algo` ("md5", "blake3", "xxh3" or "crc32").
+/// `keyed`/`derive_key` only apply to blake3 and are ignored otherwise.
+fn new_hasher(algo: &str, keyed: Option<&[u8; 32]>, derive_key: Option<&str>) -> Box<dyn Hasher> {
+    match algo {
+        "md5" => Box::new(openssl::hash::Hasher::new(openssl::hash::MessageDigest::md5()).expect("openssl md5 hasher")),
+        "xxh3" => Box::new(Xxh3::new()),
+        "crc32" => Box::new(Crc32Hasher::new()),
+        _ => Box::new(new_blake3_hasher(keyed, derive_key)),
+    }
+}
+
+/// Parses `--keyed`'s hex string into raw key bytes, if given.
+fn parse_keyed(hex_key: &str) -> io::Result<[u8; 32]> {
+    let bytes = hex::decode(hex_key).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    bytes
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "--keyed expects a 32-byte hex-encoded key"))
+}
+
+/// Short tag character written alongside the digest so `check_hash_workers`
+/// can reconstruct which algorithm produced it.
+fn algo_code(algo: &str) -> char {
+    match algo {
+        "md5" => 'm',
+        "xxh3" => 'x',
+        "crc32" => 'c',
+        _ => 'b',
+    }
+}
+
+fn algo_from_code(code: char) -> &'static str {
+    match code {
+        'm' => "md5",
+        'x' => "xxh3",
+        'c' => "crc32",
+        _ => "blake3",
+    }
+}
+
+/// Uppercase algorithm tag used by `--tag`'s BSD-style output, e.g. `MD5 (path) = hex`.
+fn bsd_tag_name(algo: &str) -> &'static str {
+    match algo {
+        "md5" => "MD5",
+        "xxh3" => "XXH3",
+        "crc32" => "CRC32",
+        _ => "BLAKE3",
+    }
+}
+
+fn bsd_algo_from_tag(tag: &str) -> Option<&'static str> {
+    match tag {
+        "MD5" => Some("md5"),
+        "XXH3" => Some("xxh3"),
+        "CRC32" => Some("crc32"),
+        "BLAKE3" => Some("blake3"),
+        _ => None,
+    }
+}
+
 fn hash_full_md5(path: &Path) -> io::Result<String> {//here, buffer reader is slower
     use md5::{Digest, Md5};
     thread::scope(|work| {
@@ -72,13 +188,45 @@ fn hash_full_openssl_md5(path: &Path) -> io::Result<String> {
     })
 }
 
-fn hash_full_blake3(path: &Path) -> io::Result<String> {
+const MMAP_THRESHOLD: u64 = 16 * MIN_READ_SIZE as u64; // 16 MiB
+
+fn hash_full_blake3(path: &Path, keyed: Option<&[u8; 32]>, derive_key: Option<&str>) -> io::Result<String> {
+    let mut hasher = new_blake3_hasher(keyed, derive_key);
     let file = File::open(path)?;
-    let mut hasher = Hasher::new();
-    hasher.update_reader(file)?;
+    if file.metadata()?.len() >= MMAP_THRESHOLD {
+        //mmap + rayon let blake3 parallelize over the whole file without a read-buffer copy
+        hasher.update_mmap_rayon(path)?;
+    } else {
+        hasher.update_reader(file)?;
+    }
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+fn hash_full_generic(path: &Path, algo: &str) -> io::Result<String> {
+    thread::scope(|work| {
+        let (in_s, in_r) = bounded(4);
+        work.spawn(move || -> io::Result<()> {
+            let mut f = File::open(path)?;
+            let mut buf = vec![0u8; 2 * MIN_READ_SIZE];
+
+            loop {
+                let n = f.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                in_s.send(buf[..n].to_owned()).unwrap();
+            }
+            Ok(())
+        });
+
+        let mut hasher = new_hasher(algo, None, None);
+        while let Ok(chunk) = in_r.recv() {
+            hasher.update(&chunk);
+        }
+        Ok(hasher.finalize())
+    })
+}
+
 #[derive(Clone, Copy, Debug)]
 struct SampleBlock {
     offset: u64,
@@ -120,16 +268,149 @@ fn make_sample_offsets(file_len: u64, speed: usize) -> Vec<SampleBlock> {
     offsets
 }
 
-fn hash_sampled_blake3_pread(path: &Path, speed: usize, threads: usize) -> io::Result<String> {
+/// Single-threaded io_uring engine for the sampled path: keeps a fixed number
+/// of `IORING_OP_READ` SQEs in flight, hashing and re-arming each slot as its
+/// CQE lands instead of spreading `pread` calls over a pool of worker threads.
+#[cfg(target_os = "linux")]
+mod io_uring_sampler {
+    use super::{new_hasher, Hasher, SampleBlock, MIN_READ_SIZE};
+    use std::{fs::File, io, os::unix::io::AsRawFd};
+
+    use io_uring::{opcode, types, IoUring};
+
+    const QUEUE_DEPTH: usize = 64;
+
+    pub fn hash_sampled(
+        file: &File,
+        jobs: &[SampleBlock],
+        file_len: u64,
+        algo: &str,
+        keyed: Option<&[u8; 32]>,
+        derive_key: Option<&str>,
+    ) -> io::Result<String> {
+        let mut ring = IoUring::new(QUEUE_DEPTH as u32)?;
+        let fd = types::Fd(file.as_raw_fd());
+
+        let depth = QUEUE_DEPTH.min(jobs.len());
+        let mut buffers: Vec<Vec<u8>> = (0..depth).map(|_| vec![0u8; MIN_READ_SIZE]).collect();
+        let mut slot_job: Vec<Option<usize>> = vec![None; depth];
+        let mut chunks: Vec<Option<String>> = vec![None; jobs.len()];
+
+        let mut next_job = 0;
+        let mut inflight = 0;
+        for slot in 0..depth {
+            submit(&mut ring, fd, &mut buffers, &mut slot_job, slot, jobs, &mut next_job)?;
+            inflight += 1;
+        }
+        ring.submit()?;
+
+        while inflight > 0 {
+            ring.submit_and_wait(1)?;
+            let completed: Vec<_> = ring.completion().map(|cqe| (cqe.user_data() as usize, cqe.result())).collect();
+
+            for (slot, result) in completed {
+                let job_idx = slot_job[slot].take().expect("completed slot had no pending job");
+                let job = jobs[job_idx];
+                let n = result.max(0) as usize;
+
+                let mut local_hasher = new_hasher(algo, keyed, derive_key);
+                local_hasher.update(&buffers[slot][..n.min(job.len)]);
+                chunks[job_idx] = Some(local_hasher.finalize());
+                inflight -= 1;
+
+                if next_job < jobs.len() {
+                    submit(&mut ring, fd, &mut buffers, &mut slot_job, slot, jobs, &mut next_job)?;
+                    inflight += 1;
+                }
+            }
+            if inflight > 0 {
+                ring.submit()?;
+            }
+        }
+
+        Ok(super::combine_sampled_chunks(chunks, file_len, algo, keyed, derive_key))
+    }
+
+    /// Pushes the next pending job's read onto `slot`, re-arming it.
+    fn submit(
+        ring: &mut IoUring,
+        fd: types::Fd,
+        buffers: &mut [Vec<u8>],
+        slot_job: &mut [Option<usize>],
+        slot: usize,
+        jobs: &[SampleBlock],
+        next_job: &mut usize,
+    ) -> io::Result<()> {
+        let job_idx = *next_job;
+        *next_job += 1;
+        let job = jobs[job_idx];
+        slot_job[slot] = Some(job_idx);
+
+        let read_e = opcode::Read::new(fd, buffers[slot].as_mut_ptr(), job.len as u32)
+            .offset(job.offset)
+            .build()
+            .user_data(slot as u64);
+        unsafe {
+            ring.submission()
+                .push(&read_e)
+                .map_err(|_| io::Error::new(io::ErrorKind::Other, "io_uring submission queue full"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Folds `file_len` plus each per-block digest (in original index order) into
+/// the final combined digest, shared by the io_uring and thread-pool paths.
+fn combine_sampled_chunks(chunks: Vec<Option<String>>, file_len: u64, algo: &str, keyed: Option<&[u8; 32]>, derive_key: Option<&str>) -> String {
+    let mut hasher = new_hasher(algo, keyed, derive_key);
+    hasher.update(&file_len.to_be_bytes());
+    for h in chunks.into_iter().flatten() {
+        hasher.update(h.as_bytes());
+    }
+    hasher.finalize()
+}
+
+fn hash_sampled_blake3_pread(
+    path: &Path,
+    speed: usize,
+    threads: usize,
+    algo: &str,
+    keyed: Option<&[u8; 32]>,
+    derive_key: Option<&str>,
+) -> io::Result<String> {
     let file = File::open(path)?;
     let meta = file.metadata()?;
     let file_len = meta.len();
 
     if file_len == 0 {
-        return Ok(hash(&[]).to_hex().to_string());
+        return Ok(if algo == "blake3" && keyed.is_none() && derive_key.is_none() {
+            hash(&[]).to_hex().to_string()
+        } else {
+            new_hasher(algo, keyed, derive_key).finalize()
+        });
+    }
+
+    let jobs = make_sample_offsets(file_len, speed);
+
+    #[cfg(target_os = "linux")]
+    if let Ok(digest) = io_uring_sampler::hash_sampled(&file, &jobs, file_len, algo, keyed, derive_key) {
+        return Ok(digest);
     }
 
-    let jobs = &make_sample_offsets(file_len, speed);
+    hash_sampled_thread_pool(&file, &jobs, file_len, threads, algo, keyed, derive_key)
+}
+
+/// Worker-pool `pread` fallback (`threads` workers sharing the job list), used
+/// on non-Linux targets and whenever the io_uring path returns an error.
+fn hash_sampled_thread_pool(
+    file: &File,
+    jobs: &[SampleBlock],
+    file_len: u64,
+    threads: usize,
+    algo: &str,
+    keyed: Option<&[u8; 32]>,
+    derive_key: Option<&str>,
+) -> io::Result<String> {
     thread::scope(|scope| {
         let counter = Arc::new(AtomicUsize::new(0));
         let (ou_s, ou_r) = bounded(threads * 2);
@@ -146,76 +427,96 @@ fn hash_sampled_blake3_pread(path: &Path, speed: usize, threads: usize) -> io::R
                     }
                     let job = &jobs[i];
                     let n = FileExt::read_at(&f, &mut buf[..job.len], job.offset).unwrap_or(0);
-                    let mut local_hasher = Hasher::new();
+                    let mut local_hasher = new_hasher(algo, keyed, derive_key);
                     local_hasher.update(&buf[..n]);
-                    let hash_bytes = local_hasher.finalize().as_bytes().to_vec();
-                    ou_s.send((i, hash_bytes)).ok();
+                    ou_s.send((i, local_hasher.finalize())).ok();
                 }
             });
         }
         drop(ou_s);
 
-        let mut chunks: Vec<Option<Vec<u8>>> = vec![None; jobs.len()];
-        while let Ok((idx, hash_bytes)) = ou_r.recv() {
-            chunks[idx] = Some(hash_bytes);
+        let mut chunks: Vec<Option<String>> = vec![None; jobs.len()];
+        while let Ok((idx, digest)) = ou_r.recv() {
+            chunks[idx] = Some(digest);
         }
 
-        let mut hasher = Hasher::new();
-        hasher.update(&file_len.to_be_bytes());
-        for h in chunks.into_iter().flatten() {
-            hasher.update(&h);
-        }
-        Ok(hasher.finalize().to_hex().to_string())
+        Ok(combine_sampled_chunks(chunks, file_len, algo, keyed, derive_key))
     })
 }
 
-fn hash_file(path: &Path, speed: usize, thread: usize) -> String {
-    if speed == 0 {
-        match hash_full_openssl_md5(path) {
-            Ok(d) => d,
-            Err(_) => String::new(),
-        }
-    }else if speed == 1 {
-        match hash_full_blake3(path) {
-            Ok(d) => d,
-            Err(_) => String::new(),
+fn hash_file(
+    path: &Path,
+    speed: usize,
+    thread: usize,
+    algo: &str,
+    keyed: Option<&[u8; 32]>,
+    derive_key: Option<&str>,
+) -> String {
+    let r = if speed == 0 {
+        hash_full_openssl_md5(path)
+    } else if speed == 1 {
+        if algo == "blake3" {
+            hash_full_blake3(path, keyed, derive_key)
+        } else {
+            hash_full_generic(path, algo)
         }
     } else {
-        let r = hash_sampled_blake3_pread(
-            path,
-            speed,                                 
-            thread.max(1),                      
-        );
-        match r {
-            Ok(d) => d,
-            Err(_) => String::new(),
-        }
+        hash_sampled_blake3_pread(path, speed, thread.max(1), algo, keyed, derive_key)
+    };
+    match r {
+        Ok(d) => d,
+        Err(_) => String::new(),
     }
 }
 
+/// Walks `opt.dest`, yielding every regular file while honoring `--hidden`/`--link`.
+fn walk_files(opt: &Opt) -> impl Iterator<Item = PathBuf> + '_ {
+    opt.dest.iter().flat_map(move |file| {
+        WalkDir::new(file)
+            .follow_links(opt.link)
+            .into_iter()
+            .filter_entry(move |e| {
+                (opt.link || !e.path_is_symlink())
+                    && (opt.hidden
+                        || !e
+                            .file_name()
+                            .to_str()
+                            .map(|s| s.starts_with('.') && s != "." && !s.starts_with("./") && !s.starts_with(".."))
+                            .unwrap_or(false))
+            })
+            .filter_map(|x| x.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.path().to_path_buf())
+    })
+}
+
+/// Parses `--keyed`'s hex string, exiting with a clear error if it isn't valid.
+fn resolve_keyed(opt: &Opt) -> Option<[u8; 32]> {
+    opt.keyed.as_deref().map(|hex_key| match parse_keyed(hex_key) {
+        Ok(k) => k,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    })
+}
+
 fn get_hash_workers(opt: &Opt) {
+    if opt.tag && opt.speed > 1 {
+        eprintln!("--tag only supports full-file digests, pass --speed 0 or --speed 1 (or drop --tag)");
+        std::process::exit(1);
+    }
+    // --algo only applies to speed levels >= 1; speed 0 always uses md5.
+    let algo = if opt.speed == 0 { "md5" } else { opt.algo.as_str() };
+
+    let keyed = resolve_keyed(opt);
+    let derive_key = opt.derive_key.as_deref();
+
     thread::scope(|work| {
         let (in_s, in_r) = bounded(opt.thread * 4);
         work.spawn(move || {
-            for file in opt.dest.iter(){
-                for entry in WalkDir::new(file)
-                    .follow_links(opt.link)
-                    .into_iter()
-                    .filter_entry(|e| {
-                        (opt.link || !e.path_is_symlink())
-                            && (opt.hidden
-                                || !e
-                                    .file_name()
-                                    .to_str()
-                                    .map(|s| s.starts_with('.') && s != "." && !s.starts_with("./") && !s.starts_with(".."))
-                                    .unwrap_or(false))
-                    })
-                    .filter_map(|x| x.ok())
-                {
-                    if entry.file_type().is_file() {
-                        in_s.send(entry.path().to_path_buf()).ok();
-                    }
-                }
+            for path in walk_files(opt) {
+                in_s.send(path).ok();
             }
         });
 
@@ -226,7 +527,7 @@ fn get_hash_workers(opt: &Opt) {
             let ou_s = ou_s.clone();
             work.spawn(move || {
                 while let Ok(path) = in_r.recv() {
-                    let digest = hash_file(&path, opt.speed as usize, opt.thread);
+                    let digest = hash_file(&path, opt.speed as usize, opt.thread, algo, keyed.as_ref(), derive_key);
                     ou_s.send((path, digest)).ok();
                 }
             });
@@ -237,17 +538,212 @@ fn get_hash_workers(opt: &Opt) {
             while let Ok((path, digest)) = ou_r.recv() {
                 if digest.is_empty() {
                     println!("{}: FAILED open or read", path.display());
-                } else if opt.speed == 0 {
+                } else if opt.tag {
+                    println!("{} ({}) = {digest}", bsd_tag_name(algo), path.display());
+                } else if opt.speed == 0 && algo == "md5" {
                         println!("{digest}  {}", path.display());
                 } else {
-                    println!("{}s{}  {}", digest, opt.speed, path.display());
+                    let mode = if algo != "blake3" {
+                        ""
+                    } else if opt.keyed.is_some() {
+                        "k"
+                    } else if opt.derive_key.is_some() {
+                        "d"
+                    } else {
+                        ""
+                    };
+                    println!("{}s{}{}{}  {}", digest, opt.speed, algo_code(algo), mode, path.display());
                 }
             }
         });
     });
 }
 
+/// First-block digest used to cheaply regroup same-length files before a full hash.
+fn partial_hash(path: &Path, algo: &str) -> String {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return String::new(),
+    };
+    let mut buf = vec![0u8; MIN_READ_SIZE];
+    let n = match FileExt::read_at(&file, &mut buf, 0) {
+        Ok(n) => n,
+        Err(_) => return String::new(),
+    };
+
+    if algo == "md5" {
+        use openssl::hash::{hash, MessageDigest};
+        return match hash(MessageDigest::md5(), &buf[..n]) {
+            Ok(d) => hex::encode(d),
+            Err(_) => String::new(),
+        };
+    }
+
+    let mut hasher = new_hasher(algo, None, None);
+    hasher.update(&buf[..n]);
+    hasher.finalize()
+}
+
+/// Runs `compute` over `paths` through the same crossbeam_channel worker-pool
+/// shape as `get_hash_workers`, grouping paths by the digest `compute` returns.
+fn group_by_digest<F>(opt: &Opt, paths: Vec<PathBuf>, compute: F) -> HashMap<String, Vec<PathBuf>>
+where
+    F: Fn(&Path) -> String + Sync,
+{
+    thread::scope(|work| {
+        let (in_s, in_r) = bounded(opt.thread * 4);
+        work.spawn(move || {
+            for path in paths {
+                in_s.send(path).ok();
+            }
+        });
+
+        let (ou_s, ou_r) = bounded::<(PathBuf, String)>(opt.thread * 4);
+        let compute = &compute;
+        for _ in 0..opt.thread {
+            let in_r = in_r.clone();
+            let ou_s = ou_s.clone();
+            work.spawn(move || {
+                while let Ok(path) = in_r.recv() {
+                    let digest = compute(&path);
+                    ou_s.send((path, digest)).ok();
+                }
+            });
+        }
+        drop(ou_s);
+
+        let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        while let Ok((path, digest)) = ou_r.recv() {
+            if !digest.is_empty() {
+                groups.entry(digest).or_default().push(path);
+            }
+        }
+        groups
+    })
+}
+
+/// `--duplicates`: reports groups of byte-identical files using a three-phase
+/// pipeline (bucket by size, regroup by a cheap partial hash, confirm with a
+/// full hash) instead of printing one checksum per path.
+fn find_duplicates(opt: &Opt) {
+    let mut by_len: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for path in walk_files(opt) {
+        if let Ok(meta) = path.metadata() {
+            by_len.entry(meta.len()).or_default().push(path);
+        }
+    }
+    let candidates: Vec<PathBuf> = by_len.into_values().filter(|group| group.len() >= 2).flatten().collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    let by_partial = group_by_digest(opt, candidates, |path| partial_hash(path, &opt.algo));
+    let collisions: Vec<PathBuf> = by_partial.into_values().filter(|group| group.len() >= 2).flatten().collect();
+    if collisions.is_empty() {
+        return;
+    }
+
+    let keyed = resolve_keyed(opt);
+    let derive_key = opt.derive_key.as_deref();
+    // Always confirm with a full-file hash, regardless of --speed: a sampled
+    // digest here could call genuinely different files "byte-identical".
+    let confirm_speed = (opt.speed as usize).min(1);
+    let by_full = group_by_digest(opt, collisions, |path| {
+        hash_file(path, confirm_speed, opt.thread, &opt.algo, keyed.as_ref(), derive_key)
+    });
+
+    for group in by_full.into_values().filter(|group| group.len() >= 2) {
+        for path in &group {
+            println!("{}", path.display());
+        }
+        println!();
+    }
+}
+
+/// A checksum line, reduced to what's needed to recompute and compare its digest.
+struct ParsedLine {
+    hex: String,
+    path: String,
+    speed: usize,
+    algo: &'static str,
+    unverifiable: bool,
+}
+
+/// Recognizes the three checksum line shapes fastMD5 must read back:
+/// - fastMD5's own `hex s<speed><algo>[k|d]  path` tag
+/// - GNU coreutils' `hex  path` / `hex *path` (always a plain full digest)
+/// - BSD-style `ALGO (path) = hex`, as produced by `--tag` or tools like b3sum
+fn parse_checksum_line(line: &str) -> Option<ParsedLine> {
+    // A path containing literal " = " or " (" can make this look like a BSD line;
+    // only commit to this shape once it's fully validated, otherwise fall through
+    // to the GNU/custom-tag parsers below instead of rejecting the whole line.
+    if let Some((left, hex)) = line.rsplit_once(" = ") {
+        let bsd = (|| {
+            let open = left.find(" (")?;
+            let algo = bsd_algo_from_tag(&left[..open])?;
+            let path = left[open + 2..].strip_suffix(')')?;
+            Some(ParsedLine {
+                hex: hex.to_string(),
+                path: path.to_string(),
+                speed: if algo == "md5" { 0 } else { 1 },
+                algo,
+                unverifiable: false,
+            })
+        })();
+        if bsd.is_some() {
+            return bsd;
+        }
+    }
+
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 2 {
+        return None;
+    }
+    let (tag, path) = (parts[0], parts[1]);
+
+    if let Some(pos) = tag.rfind('s') {
+        let (hex, suffix) = tag.split_at(pos);
+        let suffix = &suffix[1..];
+        let (suffix, unverifiable) = match suffix.chars().last() {
+            Some('k') | Some('d') => (&suffix[..suffix.len() - 1], true),
+            _ => (suffix, false),
+        };
+        // Pre-existing `s<speed>` tags (no algo letter) predate this series and must still
+        // parse; only treat the trailing char as an algo code if it actually is one.
+        let (speed_str, algo_code) = match suffix.chars().last() {
+            Some(c) if "mxcb".contains(c) => (&suffix[..suffix.len() - 1], c),
+            _ => (suffix, 'b'),
+        };
+        let speed = speed_str.parse::<usize>().ok()?;
+        return Some(ParsedLine {
+            hex: hex.to_string(),
+            path: path.to_string(),
+            speed,
+            algo: algo_from_code(algo_code),
+            unverifiable,
+        });
+    }
+
+    //GNU format: no suffix tag, so infer the algorithm from the digest length; '*' marks binary mode
+    let algo = match tag.len() {
+        64 => "blake3",
+        16 => "xxh3",
+        8 => "crc32",
+        _ => "md5",
+    };
+    Some(ParsedLine {
+        hex: tag.to_string(),
+        path: path.strip_prefix('*').unwrap_or(path).to_string(),
+        speed: if algo == "md5" { 0 } else { 1 },
+        algo,
+        unverifiable: false,
+    })
+}
+
 fn check_hash_workers(opt: &Opt) -> bool {
+    let keyed = resolve_keyed(opt);
+    let derive_key = opt.derive_key.as_deref();
+
     thread::scope(|work| {
         let (in_s, in_r) = bounded(opt.thread * 4);
         let (ou_s, ou_r) = bounded(opt.thread * 4);
@@ -266,51 +762,32 @@ fn check_hash_workers(opt: &Opt) -> bool {
 
         for _ in 0..opt.thread {
             let (in_r, ou_s) = (in_r.clone(), ou_s.clone());
+            let keyed = keyed.as_ref();
             work.spawn(move || {
                 while let Ok(line) = in_r.recv() {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() < 2 {
-                        eprintln!("Improperly formatted checksum line: {}", line);
-                        if opt.strict {
-                            std::process::exit(1);
-                        }
-                        continue;
-                    }
-
-                    let (tag, path) = (parts[0], parts[1]);
-                    let (hex, sampled_speed) = if let Some(pos) = tag.rfind('s') {
-                        let (h, s) = tag.split_at(pos);
-                        if let Ok(sp) = s[1..].parse::<usize>() {
-                            (h.to_string(), Some(sp))
-                        } else {
+                    let parsed = match parse_checksum_line(&line) {
+                        Some(p) => p,
+                        None => {
                             eprintln!("Improperly formatted checksum line: {}", line);
                             if opt.strict {
                                 std::process::exit(1);
                             }
                             continue;
                         }
-                    } else {
-                        (tag.to_string(), None)
                     };
 
-                    let res = if sampled_speed.is_none() {
-                        hash_full_openssl_md5(Path::new(path)).map(|d| d == hex)
-                    } else if sampled_speed == Some(1) {
-                        hash_full_blake3(Path::new(path)).map(|d| d == hex)
-                    }else if let Some(sp) = sampled_speed {
-                        hash_sampled_blake3_pread(Path::new(path), sp, opt.thread.max(1)).map(|d| d == hex)
+                    let res = if parsed.unverifiable && keyed.is_none() && derive_key.is_none() {
+                        Err("keyed/derived digest, cannot verify without --keyed/--derive-key")
                     } else {
-                        Err(io::Error::new(io::ErrorKind::InvalidData, "unknown digest length"))
+                        let digest = hash_file(Path::new(&parsed.path), parsed.speed, opt.thread, parsed.algo, keyed, derive_key);
+                        if digest.is_empty() {
+                            Err("open or read")
+                        } else {
+                            Ok(digest == parsed.hex)
+                        }
                     };
 
-                    match res {
-                        Ok(ok) => {
-                            ou_s.send((path.to_string(), Ok(ok))).ok();
-                        }
-                        Err(_) => {
-                            ou_s.send((path.to_string(), Err(()))).ok();
-                        }
-                    }
+                    ou_s.send((parsed.path, res)).ok();
                 }
             });
         }
@@ -332,8 +809,8 @@ fn check_hash_workers(opt: &Opt) -> bool {
                             std::process::exit(1);
                         }
                     }
-                    Err(_) => {
-                        println!("{path}: FAILED open or read");
+                    Err(reason) => {
+                        println!("{path}: FAILED {reason}");
                         has_failed = true;
                         if opt.status {
                             std::process::exit(1);
@@ -349,6 +826,46 @@ fn check_hash_workers(opt: &Opt) -> bool {
 }
 
 
+/// Best-effort: raises RLIMIT_NOFILE toward its hard maximum so a large
+/// `--thread` count and the sampled path's per-worker file clones don't hit
+/// "Too many open files" on systems with low default soft limits (BSD/macOS).
+/// Never aborts and logs nothing on success; leaves the limit untouched if
+/// getrlimit fails. On macOS a failed sysctl is ignored and the hard maximum
+/// is used as the target.
+fn raise_nofile_limit() {
+    unsafe {
+        let mut limit = std::mem::zeroed::<libc::rlimit>();
+        if libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) != 0 {
+            return;
+        }
+
+        let mut target = limit.rlim_max;
+
+        #[cfg(target_os = "macos")]
+        {
+            let mut max_per_proc: libc::c_int = 0;
+            let mut size = std::mem::size_of::<libc::c_int>();
+            if let Ok(name) = std::ffi::CString::new("kern.maxfilesperproc") {
+                if libc::sysctlbyname(
+                    name.as_ptr(),
+                    &mut max_per_proc as *mut _ as *mut libc::c_void,
+                    &mut size,
+                    std::ptr::null_mut(),
+                    0,
+                ) == 0
+                {
+                    target = target.min(max_per_proc as libc::rlim_t);
+                }
+            }
+        }
+
+        if target > limit.rlim_cur {
+            limit.rlim_cur = target;
+            libc::setrlimit(libc::RLIMIT_NOFILE, &limit);
+        }
+    }
+}
+
 fn main() {
 
     //exit on a thread to immediately end the main thread
@@ -358,11 +875,15 @@ fn main() {
         std::process::exit(1);
     }));
 
+    raise_nofile_limit();
+
     let opt = Opt::from_args();
     if opt.check {
         if check_hash_workers(&opt) {
             std::process::exit(1);
         }
+    } else if opt.duplicates {
+        find_duplicates(&opt);
     } else {
         get_hash_workers(&opt);
     }
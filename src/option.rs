@@ -15,6 +15,11 @@ pub struct Option {
     pub status: bool,  //-a
     pub strict: bool,  //-r
     pub warn: bool,    //-w
+    pub algo: String,  //--algo
+    pub keyed: std::option::Option<String>,      //--keyed
+    pub derive_key: std::option::Option<String>, //--derive-key
+    pub duplicates: bool,           //--duplicates
+    pub tag: bool,                  //--tag
 }
 
 impl Option {
@@ -95,6 +100,38 @@ impl Option {
                     .help("warn about improperly formatted checksum lines.")
                     .hide(true)
                     .action(ArgAction::SetTrue),
+            ).arg(
+                Arg::new("algo")
+                    .long("algo")
+                    .value_name("STR")
+                    .default_value(opt.algo.clone())
+                    .value_parser(["md5", "blake3", "xxh3", "crc32"])
+                    .help("hash algorithm to use for speed levels >= 1 (speed 0 always uses md5).\nmd5 = cryptographic, same digest as standard md5sum.\nblake3 = cryptographic, the default, good balance of speed and collision resistance.\nxxh3/crc32 = non-cryptographic, several times faster than blake3,\nbest when you only need to tell whether files differ."),
+            ).arg(
+                Arg::new("keyed")
+                    .long("keyed")
+                    .value_name("32-BYTE-HEX")
+                    .conflicts_with("derive-key")
+                    .help("blake3 only: initialize the hasher with a 32-byte hex-encoded key,\nproducing a MAC instead of an unkeyed digest."),
+            ).arg(
+                Arg::new("derive-key")
+                    .long("derive-key")
+                    .value_name("CONTEXT")
+                    .conflicts_with("keyed")
+                    .help("blake3 only: derive a key from CONTEXT for domain-separated content IDs,\ninstead of an unkeyed digest."),
+            ).arg(
+                Arg::new("duplicates")
+                    .long("duplicates")
+                    .conflicts_with("check")
+                    .help("report groups of byte-identical files instead of printing one checksum per path.")
+                    .action(ArgAction::SetTrue),
+            ).arg(
+                Arg::new("tag")
+                    .long("tag")
+                    .conflicts_with("keyed")
+                    .conflicts_with("derive-key")
+                    .help("create a BSD-style checksum, e.g. `BLAKE3 (FILE) = HEX`,\nas produced/consumed by tools such as md5sum --tag and b3sum --check.\nrequires --speed 0 or 1, since the BSD format has no way to mark a digest as sampled,\nand cannot be combined with --keyed/--derive-key, which it has no way to mark either.")
+                    .action(ArgAction::SetTrue),
             ).get_matches();
 
         opt.update(args)
@@ -113,6 +150,11 @@ impl Option {
             status: args.get_flag("status"),
             strict: args.get_flag("strict"),
             warn: args.get_flag("warn"),
+            algo: args.remove_one::<String>("algo").unwrap(),
+            keyed: args.remove_one::<String>("keyed"),
+            derive_key: args.remove_one::<String>("derive-key"),
+            duplicates: args.get_flag("duplicates"),
+            tag: args.get_flag("tag"),
             ..Default::default()
         }
     }
@@ -131,6 +173,11 @@ impl Default for Option {
             status: false,
             strict: false,
             warn: false,
+            algo: "blake3".to_string(),
+            keyed: None,
+            derive_key: None,
+            duplicates: false,
+            tag: false,
         }
     }
 }